@@ -3,9 +3,11 @@ use bitcoin::{
     hashes::Hash,
     secp256k1::{Keypair, Secp256k1},
     transaction::{self, InputWeightPrediction},
-    Amount, ScriptBuf, Transaction, TxOut, WScriptHash,
-    XOnlyPublicKey, Txid
+    Amount, FeeRate, ScriptBuf, Transaction, TxOut, WScriptHash,
+    Weight, XOnlyPublicKey, Txid
 };
+use rand::seq::SliceRandom;
+use rand::RngCore;
 use serde::{Deserialize, Serialize};
 use std::vec;
 use thiserror::Error;
@@ -18,14 +20,78 @@ pub enum FestivusError {
     ReqwestError,
 }
 
-#[derive(Deserialize, Serialize)]
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
-struct RecommendedFess {
-    fastest_fee: u64,
-    half_hour_fee: u64,
-    hour_fee: u64,
-    economy_fee: u64,
-    minimum_fee: u64,
+pub struct RecommendedFess {
+    pub fastest_fee: u64,
+    pub half_hour_fee: u64,
+    pub hour_fee: u64,
+    pub economy_fee: u64,
+    pub minimum_fee: u64,
+}
+
+/// A source of the five recommended-fee buckets used to project a channel-open fee.
+///
+/// The default, [`MempoolSpaceEstimator`], calls out to mempool.space; [`StaticFeeEstimator`]
+/// returns caller-supplied rates so tests and offline/regtest callers don't depend on it.
+#[allow(async_fn_in_trait)] // no Send bound needed; festivus doesn't run estimators across threads
+pub trait FeeEstimator {
+    async fn recommended_fees(&self) -> Result<RecommendedFess, FestivusError>;
+}
+
+/// Fetches recommended fees from a mempool.space (or mempool.space-compatible) instance.
+#[derive(Debug, Clone)]
+pub struct MempoolSpaceEstimator {
+    base_url: String,
+}
+
+impl MempoolSpaceEstimator {
+    /// Uses the public mempool.space instance.
+    pub fn new() -> Self {
+        Self::with_base_url("https://mempool.space")
+    }
+
+    /// Uses an alternate mempool.space-compatible instance, e.g. a testnet/signet deployment or
+    /// a self-hosted one.
+    pub fn with_base_url(base_url: impl Into<String>) -> Self {
+        Self { base_url: base_url.into() }
+    }
+}
+
+impl Default for MempoolSpaceEstimator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FeeEstimator for MempoolSpaceEstimator {
+    async fn recommended_fees(&self) -> Result<RecommendedFess, FestivusError> {
+        reqwest::get(format!("{}/api/v1/fees/recommended", self.base_url))
+            .await
+            .map_err(|_| FestivusError::ReqwestError)?
+            .json::<RecommendedFess>()
+            .await
+            .map_err(|_| FestivusError::ReqwestError)
+    }
+}
+
+/// Returns caller-supplied fee rates instead of calling out to a network fee source. Useful for
+/// tests, regtest, or any caller that already has its own fee estimate.
+#[derive(Debug, Default, Clone)]
+pub struct StaticFeeEstimator {
+    fees: RecommendedFess,
+}
+
+impl StaticFeeEstimator {
+    pub fn new(fees: RecommendedFess) -> Self {
+        Self { fees }
+    }
+}
+
+impl FeeEstimator for StaticFeeEstimator {
+    async fn recommended_fees(&self) -> Result<RecommendedFess, FestivusError> {
+        Ok(self.fees.clone())
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
@@ -35,6 +101,37 @@ pub struct ProjectedFees {
     pub hour_fee: (u64, u64),
     pub economy_fee: (u64, u64),
     pub minimum_fee: (u64, u64),
+    /// For [`ChannelType::Anchor`] opens, the extra reserve to keep on hand to bump a stuck
+    /// commitment transaction by spending one of its anchor outputs, at the fastest fee rate.
+    /// `None` for [`ChannelType::Legacy`] opens, which have no anchor output to spend.
+    pub anchor_reserve_sat: Option<u64>,
+}
+
+/// The commitment-transaction format a channel will use.
+///
+/// This doesn't change the shape of the *funding* transaction `calculate_fee` estimates — both
+/// formats fund the channel with a single 2-of-2 P2WSH output. It only determines whether a
+/// reserve for later CPFP-bumping the *commitment* transaction's anchor output is estimated
+/// alongside the funding fee, since anchor outputs live on the commitment transaction, not here.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ChannelType {
+    /// The original commitment format: no anchor outputs to later bump.
+    #[default]
+    Legacy,
+    /// BOLT 3 anchor outputs: the commitment transaction carries two additional ~330 sat
+    /// outputs meant to be spent later to CPFP-bump its confirmation.
+    Anchor,
+}
+
+/// Approximate vbytes of a transaction that spends a single anchor output to bump a stuck
+/// commitment transaction: one witness-script input plus one output paying the bumper back.
+const ANCHOR_SPEND_VBYTES: u64 = 100;
+
+/// Estimates the extra reserve (in sat) to keep on hand so an anchor channel's commitment
+/// transaction can always be bumped by spending one of its anchor outputs at `bump_fee_rate`
+/// sat/vB.
+fn anchor_bump_reserve_sat(bump_fee_rate: u64) -> u64 {
+    bump_fee_rate * ANCHOR_SPEND_VBYTES
 }
 
 #[derive(Debug, Default, Clone)]
@@ -50,7 +147,12 @@ pub struct FestivusUtxo {
     pub address: String,
     pub amount_sat: i64,
     pub pk_script: String,
-    pub outpoint: Option<FestivusOutpoint>
+    pub outpoint: Option<FestivusOutpoint>,
+    /// The weight of satisfying this UTXO's spending condition (its scriptSig plus witness),
+    /// for script types `address_type` can't describe — P2WSH, multisig, or a Taproot
+    /// script-path spend. When set, this overrides the `InputWeightPrediction` that would
+    /// otherwise be derived from `address_type`.
+    pub satisfaction_weight: Option<Weight>
 }
 
 #[derive(Debug, Clone)]
@@ -70,7 +172,263 @@ impl Default for FestivusOutpoint {
     }
 }
 
-pub async fn calculate_fee(utxos: Option<Vec<FestivusUtxo>>, amount: i64) -> Result<ProjectedFees, FestivusError> {
+/// Returns the weight prediction used to estimate the spend of a single UTXO.
+///
+/// When the caller has supplied an explicit `satisfaction_weight` (for a script type
+/// `address_type` can't describe), it is encoded as a single opaque witness element of that
+/// weight rather than guessed from `address_type`.
+fn input_weight_prediction(utxo: &FestivusUtxo) -> InputWeightPrediction {
+    if let Some(satisfaction_weight) = utxo.satisfaction_weight {
+        return InputWeightPrediction::new(0, [satisfaction_weight.to_wu() as usize]);
+    }
+
+    match utxo.address_type {
+        FestivusAddressType::Taproot => InputWeightPrediction::P2TR_KEY_DEFAULT_SIGHASH,
+        FestivusAddressType::Other => InputWeightPrediction::P2WPKH_MAX,
+    }
+}
+
+/// The value a UTXO contributes to a transaction once the fee to spend it is deducted, using
+/// `bitcoin`'s own effective-value calculation so the fixed per-input overhead (outpoint +
+/// sequence) is accounted for alongside the scriptSig/witness weight.
+///
+/// Always derives the satisfaction weight from `input_weight_prediction`, the same source the
+/// final transaction weight prediction uses, so a UTXO's `satisfaction_weight` override can't
+/// cause this and the final fee estimate to disagree on how much it costs to spend.
+fn effective_value(utxo: &FestivusUtxo, fee_rate: u64) -> i64 {
+    let satisfaction_weight = input_weight_prediction(utxo).weight();
+    let fee_rate = FeeRate::from_sat_per_vb(fee_rate).unwrap_or(FeeRate::ZERO);
+    let amount = Amount::from_sat(utxo.amount_sat.max(0) as u64);
+
+    transaction::effective_value(fee_rate, satisfaction_weight, amount)
+        .map(|value| value.to_sat())
+        .unwrap_or(i64::MIN)
+}
+
+/// A pluggable policy for picking which UTXOs fund a channel-open transaction.
+///
+/// Different callers want different tradeoffs when estimating a channel-open fee: LND's
+/// historical behavior, a privacy-preserving random draw, or a Branch-and-Bound search that
+/// tries to avoid leaving a change output. Implementing this trait lets `festivus` support all
+/// three without baking one strategy into `predict_weight_for_inputs`.
+pub trait CoinSelectionAlgorithm {
+    /// Selects a subset of `utxos` whose value covers `target_sat`, accounting for the fee
+    /// (at `fee_rate` sat/vB) required to spend each input that is selected.
+    fn coin_select(
+        &self,
+        utxos: Vec<FestivusUtxo>,
+        target_sat: i64,
+        fee_rate: u64,
+        rng: &mut impl RngCore,
+    ) -> Result<Vec<FestivusUtxo>, FestivusError>;
+}
+
+/// Selects the largest UTXOs first. This is the current/default behavior, and matches LND.
+#[derive(Debug, Default, Clone)]
+pub struct LargestFirst;
+
+impl CoinSelectionAlgorithm for LargestFirst {
+    fn coin_select(
+        &self,
+        mut utxos: Vec<FestivusUtxo>,
+        target_sat: i64,
+        fee_rate: u64,
+        _rng: &mut impl RngCore,
+    ) -> Result<Vec<FestivusUtxo>, FestivusError> {
+        utxos.sort_by_key(|utxo| std::cmp::Reverse(utxo.amount_sat));
+
+        let mut coins = Vec::new();
+        let mut effective_remaining = target_sat;
+
+        for utxo in utxos {
+            if effective_remaining <= 0 {
+                break;
+            }
+
+            let value = effective_value(&utxo, fee_rate);
+            if value <= 0 {
+                continue;
+            }
+
+            effective_remaining -= value;
+            coins.push(utxo);
+        }
+
+        if effective_remaining > 0 {
+            return Err(FestivusError::NotEnoughBitcoin);
+        }
+
+        Ok(coins)
+    }
+}
+
+/// Shuffles the UTXOs into a random order and selects until the target is covered.
+///
+/// Picking in random order (rather than always the largest or smallest coins) avoids leaking
+/// information about a wallet's UTXO set through its coin selection, per BIP-style "single
+/// random draw" selection.
+#[derive(Debug, Default, Clone)]
+pub struct SingleRandomDraw;
+
+impl CoinSelectionAlgorithm for SingleRandomDraw {
+    fn coin_select(
+        &self,
+        mut utxos: Vec<FestivusUtxo>,
+        target_sat: i64,
+        fee_rate: u64,
+        rng: &mut impl RngCore,
+    ) -> Result<Vec<FestivusUtxo>, FestivusError> {
+        utxos.shuffle(rng);
+
+        let mut coins = Vec::new();
+        let mut effective_remaining = target_sat;
+
+        for utxo in utxos {
+            if effective_remaining <= 0 {
+                break;
+            }
+            effective_remaining -= effective_value(&utxo, fee_rate);
+            coins.push(utxo);
+        }
+
+        if effective_remaining > 0 {
+            return Err(FestivusError::NotEnoughBitcoin);
+        }
+
+        Ok(coins)
+    }
+}
+
+/// The maximum number of nodes the Branch-and-Bound search will visit before giving up.
+const BNB_ITERATION_CAP: u32 = 100_000;
+
+/// Searches for a changeless selection of UTXOs via Branch-and-Bound, falling back to
+/// `SingleRandomDraw` when no changeless selection is found within the iteration cap.
+///
+/// The search works in *effective value* space (`amount_sat - fee_rate * input_vbytes`), so
+/// a UTXO that costs more to spend than it contributes is discarded outright. At each step the
+/// search branches on including or excluding the current coin, pruning whenever the running
+/// total has overshot `target + cost_of_change` or can no longer reach `target` even by
+/// including every remaining coin. Among all selections it visits, it keeps the one with the
+/// least waste (closest to `target` from above), matching Bitcoin Core's `SelectCoinsBnB`.
+#[derive(Debug, Default, Clone)]
+pub struct BranchAndBound;
+
+impl BranchAndBound {
+    /// The acceptable slack (in sat) above `target` before a selection is considered to have
+    /// "change" rather than being a changeless hit.
+    const COST_OF_CHANGE: i64 = 148;
+
+    fn search(&self, utxos: &[(FestivusUtxo, i64)], target: i64) -> Option<Vec<FestivusUtxo>> {
+        // Suffix sums of effective value, so remaining-available lookups are O(1).
+        let mut remaining_available = vec![0i64; utxos.len() + 1];
+        for i in (0..utxos.len()).rev() {
+            remaining_available[i] = remaining_available[i + 1] + utxos[i].1;
+        }
+
+        let mut search = BnbSearch {
+            utxos,
+            remaining_available: &remaining_available,
+            target,
+            upper_bound: target + Self::COST_OF_CHANGE,
+            best: None,
+            iterations: 0,
+        };
+
+        let mut selected = Vec::new();
+        search.recurse(0, 0, &mut selected);
+
+        search
+            .best
+            .map(|(_, indices)| indices.into_iter().map(|i| utxos[i].0.clone()).collect())
+    }
+}
+
+/// Mutable search state threaded through `BranchAndBound`'s recursive descent.
+struct BnbSearch<'a> {
+    utxos: &'a [(FestivusUtxo, i64)],
+    remaining_available: &'a [i64],
+    target: i64,
+    upper_bound: i64,
+    best: Option<(i64, Vec<usize>)>,
+    iterations: u32,
+}
+
+impl BnbSearch<'_> {
+    fn recurse(&mut self, index: usize, running_total: i64, selected: &mut Vec<usize>) {
+        self.iterations += 1;
+        if self.iterations > BNB_ITERATION_CAP {
+            return;
+        }
+
+        if running_total > self.upper_bound {
+            return;
+        }
+
+        if running_total >= self.target {
+            let waste = running_total - self.target;
+            if self.best.as_ref().is_none_or(|(best_waste, _)| waste < *best_waste) {
+                self.best = Some((waste, selected.clone()));
+            }
+            if waste == 0 {
+                return;
+            }
+        }
+
+        if index == self.utxos.len() {
+            return;
+        }
+        if running_total + self.remaining_available[index] < self.target {
+            return;
+        }
+
+        // Branch: include the current coin.
+        selected.push(index);
+        self.recurse(index + 1, running_total + self.utxos[index].1, selected);
+        selected.pop();
+
+        // Branch: exclude the current coin.
+        self.recurse(index + 1, running_total, selected);
+    }
+}
+
+impl CoinSelectionAlgorithm for BranchAndBound {
+    fn coin_select(
+        &self,
+        utxos: Vec<FestivusUtxo>,
+        target_sat: i64,
+        fee_rate: u64,
+        rng: &mut impl RngCore,
+    ) -> Result<Vec<FestivusUtxo>, FestivusError> {
+        let mut candidates: Vec<(FestivusUtxo, i64)> = utxos
+            .iter()
+            .cloned()
+            .map(|utxo| {
+                let value = effective_value(&utxo, fee_rate);
+                (utxo, value)
+            })
+            .filter(|(_, value)| *value > 0)
+            .collect();
+
+        candidates.sort_by_key(|(_, value)| std::cmp::Reverse(*value));
+
+        if let Some(selection) = self.search(&candidates, target_sat) {
+            return Ok(selection);
+        }
+
+        // No changeless selection within the search cap; fall back to a random draw.
+        SingleRandomDraw.coin_select(utxos, target_sat, fee_rate, rng)
+    }
+}
+
+pub async fn calculate_fee(
+    utxos: Option<Vec<FestivusUtxo>>,
+    amount: i64,
+    fee_estimator: impl FeeEstimator,
+    channel_type: ChannelType,
+    algorithm: &impl CoinSelectionAlgorithm,
+    rng: &mut impl RngCore,
+) -> Result<ProjectedFees, FestivusError> {
     // Create a random taproot keypair for the ouput.
     let secp = Secp256k1::new();
     let mut rand = rand::thread_rng();
@@ -78,7 +436,8 @@ pub async fn calculate_fee(utxos: Option<Vec<FestivusUtxo>>, amount: i64) -> Res
     let keypair = Keypair::from_secret_key(&secp, &secret_key);
     let (pubkey, _) = XOnlyPublicKey::from_keypair(&keypair);
 
-    // The channel open output, P2WSH
+    // The channel open output, P2WSH. Identical for both channel types: anchor outputs live on
+    // the commitment transaction the funding output later pays into, not on this transaction.
     let funding_output = TxOut {
         value: Amount::from_sat(336),
         script_pubkey: ScriptBuf::new_p2wsh(&WScriptHash::hash(&[0u8; 43])),
@@ -101,79 +460,164 @@ pub async fn calculate_fee(utxos: Option<Vec<FestivusUtxo>>, amount: i64) -> Res
     let utxos = match utxos {
         Some(u) => u,
         None => {
+            // No real UTXO set to estimate from; stand in a single taproot coin large enough
+            // to always cover `amount` plus its own fee, so this is purely a weight estimate
+            // and never trips the balance check below.
             let mut utxo = FestivusUtxo::default();
-            utxo.amount_sat = amount;
+            utxo.amount_sat = amount.saturating_add(amount).saturating_add(1_000_000);
             utxo.outpoint = Some(FestivusOutpoint::default());
             utxo.address_type = FestivusAddressType::Taproot;
             vec![utxo]
         }
     };
-    let inputs = predict_weight_for_inputs(utxos, amount)?;
+    // The cost (in vbytes) of the transaction without any inputs (its fixed header plus the
+    // outputs), used to size the fee the selected inputs must also cover on top of the channel
+    // amount itself.
+    let output_vbytes = transaction::predict_weight(Vec::<InputWeightPrediction>::new(), txn.script_pubkey_lens())
+        .to_vbytes_ceil();
 
-    let weight = transaction::predict_weight(inputs, txn.script_pubkey_lens());
+    // Get fees
+    let fees = fee_estimator.recommended_fees().await?;
 
-    let virtual_bytes = weight.to_vbytes_ceil();
+    // Derive the projected fee at each bucket from the input set that bucket's rate would
+    // actually select, rather than re-pricing a single fixed input set at every rate.
+    let mut project = |utxos: Vec<FestivusUtxo>, sat_per_vb: u64| -> Result<(u64, u64), FestivusError> {
+        let fee_rate = FeeRate::from_sat_per_vb(sat_per_vb).unwrap_or(FeeRate::ZERO);
+        let output_fee = (output_vbytes * sat_per_vb) as i64;
+        let target_amount = amount + output_fee;
 
-    // Get fees
-    let fees = reqwest::get("https://mempool.space/api/v1/fees/recommended")
-        .await
-        .map_err(|_| FestivusError::ReqwestError)?
-        .json::<RecommendedFess>()
-        .await
-        .map_err(|_| FestivusError::ReqwestError)?;
+        let inputs = predict_weight_for_inputs(utxos, target_amount, fee_rate, algorithm, rng)?;
+        let weight = transaction::predict_weight(inputs, txn.script_pubkey_lens());
+        let fee = fee_rate.fee_wu(weight).unwrap_or(Amount::ZERO).to_sat();
+
+        Ok((fee, sat_per_vb))
+    };
+
+    let fastest_fee = project(utxos.clone(), fees.fastest_fee)?;
+
+    let anchor_reserve_sat = match channel_type {
+        ChannelType::Legacy => None,
+        ChannelType::Anchor => Some(anchor_bump_reserve_sat(fastest_fee.1)),
+    };
 
     // Calc total amount
     Ok(ProjectedFees {
-        fastest_fee: (virtual_bytes * fees.fastest_fee, fees.fastest_fee),
-        half_hour_fee: (virtual_bytes * fees.half_hour_fee, fees.half_hour_fee),
-        hour_fee: (virtual_bytes * fees.hour_fee, fees.hour_fee),
-        economy_fee: (virtual_bytes * fees.economy_fee, fees.economy_fee),
-        minimum_fee: (virtual_bytes * fees.minimum_fee, fees.minimum_fee),
+        fastest_fee,
+        half_hour_fee: project(utxos.clone(), fees.half_hour_fee)?,
+        hour_fee: project(utxos.clone(), fees.hour_fee)?,
+        economy_fee: project(utxos.clone(), fees.economy_fee)?,
+        minimum_fee: project(utxos, fees.minimum_fee)?,
+        anchor_reserve_sat,
     })
 }
 
-fn predict_weight_for_inputs(mut utxos: Vec<FestivusUtxo>, amount: i64) -> Result<Vec<InputWeightPrediction>, FestivusError> {
-    // Sort the UTXO's for largest first selection.
-    // This is the default coin selection algorithm for LND
-    utxos.sort_by(|a, b| b.amount_sat.cmp(&a.amount_sat));
+fn predict_weight_for_inputs(
+    utxos: Vec<FestivusUtxo>,
+    target_amount: i64,
+    fee_rate: FeeRate,
+    algorithm: &impl CoinSelectionAlgorithm,
+    rng: &mut impl RngCore,
+) -> Result<Vec<InputWeightPrediction>, FestivusError> {
+    let sat_per_vb = fee_rate.to_sat_per_vb_ceil();
 
-    // The coins selected for the transaction.
-    let mut coins = Vec::<FestivusUtxo>::new();
-    // If the coins fulfill requirement for the transaction.
-    let mut amount_remaining = amount;
+    let coins = algorithm.coin_select(utxos, target_amount, sat_per_vb, rng)?;
 
-    // Iterate over the provided utxos and select the coins used for the transaction.
-    utxos.iter().for_each(|utxo| {
-        if amount_remaining > 0 {
-            coins.push(utxo.clone());
-            amount_remaining -= utxo.amount_sat;
-        }
-    });
+    // From each UTXO used, get the weight prediction.
+    Ok(coins.iter().map(input_weight_prediction).collect())
+}
 
-    // Not enough BTC for the transaction in the wallet.
-    if amount_remaining > 0 {
-        return Err(FestivusError::NotEnoughBitcoin);
-    }
+/// The minimum relay fee increment (in sat/vB) a replacement transaction must add on top of the
+/// original's fee, per BIP 125 rule 4.
+const INCREMENTAL_RELAY_FEERATE: u64 = 1;
+
+/// How a pending, unconfirmed transaction should be fee-bumped.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FeeBumpMethod {
+    /// Replace the original transaction outright (BIP 125). `new_vbytes` is the size of the
+    /// replacement, which is usually close to the original's but can differ if inputs or
+    /// outputs are added to cover the higher fee.
+    Rbf { new_vbytes: u64 },
+    /// Broadcast a child transaction that spends an output of the still-unconfirmed parent, so
+    /// the pair's combined feerate clears the target. `child_vbytes` is the size of that child
+    /// before any extra funding inputs are added to pay its own fee.
+    Cpfp { child_vbytes: u64 },
+}
 
-    // From each UTXO used, get the weight prediction.
-    let txin = coins
-        .iter()
-        .map(|utxo| {
-            match utxo.address_type {
-                FestivusAddressType::Taproot => InputWeightPrediction::P2TR_KEY_DEFAULT_SIGHASH,
-                FestivusAddressType::Other => InputWeightPrediction::P2WPKH_MAX,
+/// The extra fee required to bump a pending transaction to `target_fee_rate`, and the resulting
+/// total once that extra fee is paid.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FeeBump {
+    pub additional_fee_sat: u64,
+    pub new_total_fee_sat: u64,
+}
+
+/// Calculates the fee needed to bump a pending channel-open (or any other) transaction that is
+/// confirming slower than `target_fee_rate`, via either RBF or CPFP.
+///
+/// `original_vbytes` and `original_fee_sat` describe the transaction as it was originally
+/// broadcast. For CPFP, the extra fee returned is what the *child* transaction must pay; the
+/// caller still needs to fund that child (see [`select_cpfp_funding_inputs`]).
+pub fn calculate_bump_fee(
+    original_vbytes: u64,
+    original_fee_sat: u64,
+    target_fee_rate: u64,
+    method: FeeBumpMethod,
+) -> FeeBump {
+    match method {
+        FeeBumpMethod::Rbf { new_vbytes } => {
+            // BIP 125 rule 4: the replacement must pay at least the original fee plus the
+            // minimum incremental relay fee for its own size.
+            let min_total_fee_sat =
+                original_fee_sat + INCREMENTAL_RELAY_FEERATE * new_vbytes;
+            let target_total_fee_sat = target_fee_rate * new_vbytes;
+            let new_total_fee_sat = target_total_fee_sat.max(min_total_fee_sat);
+
+            FeeBump {
+                additional_fee_sat: new_total_fee_sat - original_fee_sat,
+                new_total_fee_sat,
             }
-        })
-        .collect::<Vec<InputWeightPrediction>>();
+        }
+        FeeBumpMethod::Cpfp { child_vbytes } => {
+            // Solve for the child fee that brings the combined package feerate,
+            // (parent_fee + child_fee) / (parent_vbytes + child_vbytes), up to the target.
+            let package_vbytes = original_vbytes + child_vbytes;
+            let target_package_fee_sat = target_fee_rate * package_vbytes;
+            let child_fee_sat = target_package_fee_sat.saturating_sub(original_fee_sat);
+
+            FeeBump {
+                additional_fee_sat: child_fee_sat,
+                new_total_fee_sat: original_fee_sat + child_fee_sat,
+            }
+        }
+    }
+}
 
-    
-    Ok(txin)
+/// Selects UTXOs to fund a CPFP child transaction's fee, on top of whatever value the child
+/// already carries by spending an output of the stuck parent transaction.
+pub fn select_cpfp_funding_inputs(
+    child_fee_sat: i64,
+    fee_rate: u64,
+    utxos: Vec<FestivusUtxo>,
+    algorithm: &impl CoinSelectionAlgorithm,
+    rng: &mut impl RngCore,
+) -> Result<Vec<FestivusUtxo>, FestivusError> {
+    algorithm.coin_select(utxos, child_fee_sat, fee_rate, rng)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn test_fee_estimator() -> StaticFeeEstimator {
+        StaticFeeEstimator::new(RecommendedFess {
+            fastest_fee: 10,
+            half_hour_fee: 8,
+            hour_fee: 6,
+            economy_fee: 3,
+            minimum_fee: 1,
+        })
+    }
+
     #[tokio::test]
     async fn calc_fee_p2tr_inputs() {
         let mut utxo_one = FestivusUtxo::default();
@@ -186,7 +630,7 @@ mod tests {
 
         let utxos = vec![utxo_one, utxo_two];
 
-        let fees = calculate_fee(Some(utxos), 19_000).await;
+        let fees = calculate_fee(Some(utxos), 19_000, test_fee_estimator(), ChannelType::Legacy, &LargestFirst, &mut rand::thread_rng()).await;
 
         assert_eq!(fees.is_ok(), true)
     }
@@ -203,14 +647,14 @@ mod tests {
 
         let utxos = vec![utxo_one, utxo_two];
 
-        let fees = calculate_fee(Some(utxos), 19_000).await;
+        let fees = calculate_fee(Some(utxos), 19_000, test_fee_estimator(), ChannelType::Legacy, &LargestFirst, &mut rand::thread_rng()).await;
 
         assert_eq!(fees.is_ok(), true)
     }
 
     #[tokio::test]
     async fn no_utxos() {
-        let fees = calculate_fee(None, 19_000).await;
+        let fees = calculate_fee(None, 19_000, test_fee_estimator(), ChannelType::Legacy, &LargestFirst, &mut rand::thread_rng()).await;
 
         assert_eq!(fees.is_ok(), true)
     }
@@ -228,7 +672,7 @@ mod tests {
 
         let utxos = vec![utxo_one, utxo_two];
 
-        let fees = calculate_fee(Some(utxos), 125_000_000).await;
+        let fees = calculate_fee(Some(utxos), 125_000_000, test_fee_estimator(), ChannelType::Legacy, &LargestFirst, &mut rand::thread_rng()).await;
 
         assert_eq!(fees.is_ok(), true)
     }
@@ -245,8 +689,171 @@ mod tests {
 
         let utxos = vec![utxo_one, utxo_two];
 
-        let fees = calculate_fee(Some(utxos), 19_000).await;
+        let fees = calculate_fee(Some(utxos), 19_000, test_fee_estimator(), ChannelType::Legacy, &LargestFirst, &mut rand::thread_rng()).await;
 
         assert_eq!(fees.is_err(), true)
     }
+
+    #[tokio::test]
+    async fn anchor_channel_adds_reserve_without_changing_funding_tx_fee() {
+        let mut utxo_one = FestivusUtxo::default();
+        utxo_one.amount_sat = Amount::from_btc(3.6).unwrap().to_sat() as i64;
+        utxo_one.outpoint = Some(FestivusOutpoint::default());
+        utxo_one.address_type = FestivusAddressType::Taproot;
+
+        let mut utxo_two = FestivusUtxo::default();
+        utxo_two.amount_sat = Amount::from_btc(1.2).unwrap().to_sat() as i64;
+
+        let legacy_fees = calculate_fee(
+            Some(vec![utxo_one.clone(), utxo_two.clone()]),
+            19_000,
+            test_fee_estimator(),
+            ChannelType::Legacy,
+            &LargestFirst,
+            &mut rand::thread_rng(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(legacy_fees.anchor_reserve_sat, None);
+
+        let anchor_fees = calculate_fee(
+            Some(vec![utxo_one, utxo_two]),
+            19_000,
+            test_fee_estimator(),
+            ChannelType::Anchor,
+            &LargestFirst,
+            &mut rand::thread_rng(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            anchor_fees.anchor_reserve_sat,
+            Some(anchor_bump_reserve_sat(anchor_fees.fastest_fee.1))
+        );
+        // Anchor outputs live on the commitment transaction, not the funding transaction, so
+        // the funding-tx fee itself is identical between channel types.
+        assert_eq!(anchor_fees.fastest_fee, legacy_fees.fastest_fee);
+    }
+
+    fn utxo(amount_sat: i64) -> FestivusUtxo {
+        let mut utxo = FestivusUtxo::default();
+        utxo.amount_sat = amount_sat;
+        utxo.address_type = FestivusAddressType::Taproot;
+        utxo
+    }
+
+    #[test]
+    fn largest_first_picks_biggest_coins_first() {
+        let utxos = vec![utxo(10_000), utxo(50_000), utxo(20_000)];
+        let mut rng = rand::thread_rng();
+
+        let selected = LargestFirst.coin_select(utxos, 60_000, 1, &mut rng).unwrap();
+
+        assert_eq!(selected.len(), 2);
+        assert_eq!(selected[0].amount_sat, 50_000);
+        assert_eq!(selected[1].amount_sat, 20_000);
+    }
+
+    #[test]
+    fn single_random_draw_covers_target() {
+        let utxos = vec![utxo(10_000), utxo(50_000), utxo(20_000)];
+        let mut rng = rand::thread_rng();
+
+        let selected = SingleRandomDraw.coin_select(utxos, 15_000, 1, &mut rng).unwrap();
+
+        let total: i64 = selected.iter().map(|u| effective_value(u, 1)).sum();
+        assert!(total >= 15_000);
+    }
+
+    #[test]
+    fn branch_and_bound_finds_exact_match() {
+        let utxos = vec![utxo(10_000), utxo(15_000), utxo(30_000)];
+        let mut rng = rand::thread_rng();
+
+        // A zero fee rate keeps effective value equal to the nominal amount, so an exact
+        // combination of inputs is reachable.
+        let selected = BranchAndBound.coin_select(utxos, 25_000, 0, &mut rng).unwrap();
+
+        let total: i64 = selected.iter().map(|u| u.amount_sat).sum();
+        assert_eq!(total, 25_000);
+    }
+
+    #[test]
+    fn branch_and_bound_falls_back_when_no_changeless_match() {
+        // No subset of {10_000, 999_000} lands within COST_OF_CHANGE of 500_000 — the 999_000
+        // coin alone would leave ~499_000 sat of change — so the search itself must report no
+        // changeless hit rather than settle for that overshoot.
+        let mut candidates: Vec<(FestivusUtxo, i64)> = vec![utxo(10_000), utxo(999_000)]
+            .into_iter()
+            .map(|u| {
+                let value = effective_value(&u, 1);
+                (u, value)
+            })
+            .collect();
+        candidates.sort_by_key(|(_, value)| std::cmp::Reverse(*value));
+
+        assert!(BranchAndBound.search(&candidates, 500_000).is_none());
+
+        // With no changeless match, coin_select must still succeed by falling back to
+        // SingleRandomDraw rather than returning the rejected overshoot or erroring out.
+        let utxos = vec![utxo(10_000), utxo(999_000)];
+        let mut rng = rand::thread_rng();
+        let selected = BranchAndBound.coin_select(utxos, 500_000, 1, &mut rng).unwrap();
+
+        let total: i64 = selected.iter().map(|u| effective_value(u, 1)).sum();
+        assert!(total >= 500_000);
+    }
+
+    #[test]
+    fn satisfaction_weight_override_beats_address_type_guess() {
+        let mut multisig_utxo = utxo(100_000);
+        multisig_utxo.address_type = FestivusAddressType::Other;
+        multisig_utxo.satisfaction_weight = Some(Weight::from_wu(900));
+
+        let default_value = effective_value(&utxo(100_000), 1);
+        let overridden_value = effective_value(&multisig_utxo, 1);
+
+        assert!(overridden_value < default_value);
+    }
+
+    #[test]
+    fn rbf_bump_enforces_minimum_incremental_relay_fee() {
+        // Original paid 5 sat/vB; the target rate is unchanged, so the bump should be driven by
+        // the BIP 125 minimum incremental relay fee rather than the target rate.
+        let bump = calculate_bump_fee(200, 1_000, 5, FeeBumpMethod::Rbf { new_vbytes: 200 });
+
+        assert_eq!(bump.additional_fee_sat, 200);
+        assert_eq!(bump.new_total_fee_sat, 1_200);
+    }
+
+    #[test]
+    fn rbf_bump_targets_higher_fee_rate() {
+        let bump = calculate_bump_fee(200, 1_000, 20, FeeBumpMethod::Rbf { new_vbytes: 200 });
+
+        assert_eq!(bump.new_total_fee_sat, 4_000);
+        assert_eq!(bump.additional_fee_sat, 3_000);
+    }
+
+    #[test]
+    fn cpfp_bump_solves_for_combined_package_feerate() {
+        // Parent is 200 vbytes and paid 1 sat/vB; a 150 vbyte child must raise the combined
+        // package to 10 sat/vB.
+        let bump = calculate_bump_fee(200, 200, 10, FeeBumpMethod::Cpfp { child_vbytes: 150 });
+
+        assert_eq!(bump.new_total_fee_sat, 3_500);
+        assert_eq!(bump.additional_fee_sat, 3_300);
+    }
+
+    #[test]
+    fn select_cpfp_funding_inputs_covers_child_fee() {
+        let utxos = vec![utxo(1_000), utxo(5_000)];
+        let mut rng = rand::thread_rng();
+
+        let selected =
+            select_cpfp_funding_inputs(3_300, 10, utxos, &LargestFirst, &mut rng).unwrap();
+
+        let total: i64 = selected.iter().map(|u| u.amount_sat).sum();
+        assert!(total >= 3_300);
+    }
 }